@@ -1,14 +1,27 @@
 //! Provides a macro `assert_within!` for tests involving floating point numbers.
 //!
 //! ```rust
-//! assert_within!(+0.001, val, target, "Value was not within additive 0.001: {more} {context}");
-//! assert_within!(~0.05, val, target, "Value was not within 5% of target: {additional} {information:?}");
+//! use assert_within::assert_within;
+//!
+//! let val = 1.0005;
+//! let target: f64 = 1.0;
+//! let close = f64::from_bits(target.to_bits() + 2);
+//! let more = "additional context";
+//!
+//! assert_within!(+0.001, val, target, "Value was not within additive 0.001: {more}");
+//! assert_within!(~0.05, val, target, "Value was not within 5% of target: {more}");
+//! assert_within!(+~ 1e-3, 1e-3, val, target, "Value was not within atol + rtol * target: {more}");
+//! assert_within!(ulps 4, close, target, "Value was not within 4 ulps of target: {more}");
 //! ```
 //!
 //! Highlights include:
 //!
 //! * Pass arguments by reference or value
-//! * Sigils (+, ~) indicate additive or relative error
+//! * Sigils (+, ~) indicate additive or relative error, and (+~) combines both (numpy `isclose` style)
+//! * `ulps` compares bit-exact distance in units in the last place, for `f32`/`f64`
+//! * `assert_all_within!` applies the same checks elementwise across two slices or iterables
+//! * `check_within!` and [`within_add`]/[`within_mul`] expose the same checks as a `Result`
+//!   instead of panicking
 //! * Traps Nan in any of the arguments
 //! * Errors cause both the stringified expressions and their values to be displayed
 //! * Arbitrary additional format args
@@ -23,7 +36,9 @@ use core::{
 };
 use num_traits::float::FloatCore;
 
-/// Helper for asserting that an f64 value is within +/- epsilon of another, additively
+/// Helper for asserting that an f64 value is within +/- epsilon of another, additively. Thin
+/// panicking wrapper around [`within_add`], so the pass/fail predicate lives in exactly one
+/// place.
 #[doc(hidden)]
 #[allow(clippy::too_many_arguments)]
 pub fn assert_within_add_impl<N: Display + FloatCore>(
@@ -36,15 +51,15 @@ pub fn assert_within_add_impl<N: Display + FloatCore>(
     eps: impl Borrow<N>,
     context: fmt::Arguments,
 ) {
-    let val = val.borrow();
-    let target = target.borrow();
-    let eps = eps.borrow();
+    let Err(WithinError { val, target, eps, .. }) = within_add(val, target, eps) else {
+        return;
+    };
 
     if eps.is_nan() {
         panic!("assert_within failed at {file}:{line}:\nepsilon was Nan: {eps}\n{context}");
     }
 
-    if *eps < N::zero() {
+    if eps < N::zero() {
         panic!(
             "assert_within failed at {file}:{line}:\nEpsilon cannot be negative when used with assert_within! macro: {eps}\n{context}"
         )
@@ -60,46 +75,459 @@ pub fn assert_within_add_impl<N: Display + FloatCore>(
         );
     }
 
-    if *val < *target - *eps {
+    let diff = (val - target).abs();
+    let overshoot = diff - eps;
+
+    if val < target - eps {
+        panic!(
+            "assert_within failed at {file}:{line}:\n`{val_str}` was less than `{target_str}` - {eps})\nleft:  {val}\nright: {target}\ndiff:    {diff}\nallowed: {eps}\novershoot: {overshoot}\n{context}"
+        );
+    }
+
+    panic!(
+        "assert_within failed at {file}:{line}:\n`{val_str}` was greater than `{target_str}` + {eps})\nleft:  {val}\nright: {target}\ndiff:    {diff}\nallowed: {eps}\novershoot: {overshoot}\n{context}"
+    );
+}
+
+/// Helper for asserting that an f64 value is within +/- epsilon of another, multiplicatively.
+/// Thin panicking wrapper around [`within_mul`], so the pass/fail predicate lives in exactly one
+/// place.
+#[doc(hidden)]
+#[allow(clippy::too_many_arguments)]
+pub fn assert_within_mul_impl<N: Display + FloatCore>(
+    file: &'static str,
+    line: u32,
+    val: impl Borrow<N>,
+    val_str: &'static str,
+    target: impl Borrow<N>,
+    target_str: &'static str,
+    eps: impl Borrow<N>,
+    context: fmt::Arguments,
+) {
+    let Err(WithinError { val, target, eps, .. }) = within_mul(val, target, eps) else {
+        return;
+    };
+
+    if eps.is_nan() {
+        panic!("assert_within failed at {file}:{line}:\nepsilon was Nan: {eps}\n{context}");
+    }
+
+    if eps < N::zero() {
+        panic!(
+            "assert_within failed at {file}:{line}:\nEpsilon cannot be negative when used with assert_within! macro: {eps}\n{context}"
+        )
+    }
+
+    if val.is_nan() {
+        panic!("assert_within failed at {file}:{line}:\n`{val_str}` was Nan: {val}\n{context}");
+    }
+
+    if target.is_nan() {
         panic!(
-            "assert_within failed at {file}:{line}:\n`{val_str}` was less than `{target_str}` - {eps})\nleft:  {val}\nright: {target}\n{context}"
+            "assert_within failed at {file}:{line}:\n`{target_str}` was Nan: {target}\n{context}"
         );
     }
 
-    if *val > *target + *eps {
+    let relative_error = (val / target - N::one()).abs();
+
+    if val < (N::one() - eps) * target {
         panic!(
-            "assert_within failed at {file}:{line}:\n`{val_str}` was greater than `{target_str}` + {eps})\nleft:  {val}\nright: {target}\n{context}"
+            "assert_within failed at {file}:{line}:\n`{val_str}` was less than (1 ± {eps}) * `{target_str}`\nleft:  {val}\nright: {target}\nrelative error: {relative_error}\nallowed:        {eps}\n{context}"
         );
     }
+
+    panic!(
+        "assert_within failed at {file}:{line}:\n`{val_str}` was greater than (1 ± {eps}) * `{target_str}`\nleft:  {val}\nright: {target}\nrelative error: {relative_error}\nallowed:        {eps}\n{context}"
+    );
 }
 
-/// Helper for asserting that an f64 value is within +/- epsilon of another, multiplicatively
+/// Helper for asserting that an f64 value is within `atol + rtol * |target|` of another,
+/// combining absolute and relative tolerance (numpy `isclose` style). Unlike the purely
+/// multiplicative mode, this stays meaningful when `target` is near zero, since `atol`
+/// provides a floor that `rtol * |target|` cannot collapse to nothing.
 #[doc(hidden)]
 #[allow(clippy::too_many_arguments)]
-pub fn assert_within_mul_impl<N: Display + FloatCore>(
+pub fn assert_within_close_impl<N: Display + FloatCore>(
     file: &'static str,
     line: u32,
     val: impl Borrow<N>,
     val_str: &'static str,
     target: impl Borrow<N>,
     target_str: &'static str,
-    eps: impl Borrow<N>,
+    atol: impl Borrow<N>,
+    rtol: impl Borrow<N>,
     context: fmt::Arguments,
 ) {
     let val = val.borrow();
     let target = target.borrow();
+    let atol = atol.borrow();
+    let rtol = rtol.borrow();
+
+    if atol.is_nan() {
+        panic!("assert_within failed at {file}:{line}:\natol was Nan: {atol}\n{context}");
+    }
+
+    if *atol < N::zero() {
+        panic!(
+            "assert_within failed at {file}:{line}:\natol cannot be negative when used with assert_within! macro: {atol}\n{context}"
+        )
+    }
+
+    if rtol.is_nan() {
+        panic!("assert_within failed at {file}:{line}:\nrtol was Nan: {rtol}\n{context}");
+    }
+
+    if *rtol < N::zero() {
+        panic!(
+            "assert_within failed at {file}:{line}:\nrtol cannot be negative when used with assert_within! macro: {rtol}\n{context}"
+        )
+    }
+
+    if val.is_nan() {
+        panic!("assert_within failed at {file}:{line}:\n`{val_str}` was Nan: {val}\n{context}");
+    }
+
+    if target.is_nan() {
+        panic!(
+            "assert_within failed at {file}:{line}:\n`{target_str}` was Nan: {target}\n{context}"
+        );
+    }
+
+    let diff = (*val - *target).abs();
+    let tolerance = *atol + *rtol * target.abs();
+
+    if diff > tolerance {
+        panic!(
+            "assert_within failed at {file}:{line}:\n`{val_str}` was not within atol + rtol * |`{target_str}`| of `{target_str}`\nleft:  {val}\nright: {target}\n|left - right|:     {diff}\natol + rtol * |right|: {tolerance}\n{context}"
+        );
+    }
+}
+
+/// The comparison mode carried by a [`WithinError`], mirroring the `assert_within!` sigils.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WithinMode {
+    /// `+`: additive tolerance.
+    Add,
+    /// `~`: multiplicative tolerance.
+    Mul,
+}
+
+/// Error returned by [`within_add`] and [`within_mul`] when `val` is not within tolerance of
+/// `target`, or when `eps` itself is invalid (Nan or negative).
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct WithinError<N> {
+    pub val: N,
+    pub target: N,
+    pub eps: N,
+    pub mode: WithinMode,
+}
+
+impl<N: Display + FloatCore> fmt::Display for WithinError<N> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let Self {
+            val,
+            target,
+            eps,
+            mode,
+        } = *self;
+
+        if eps.is_nan() {
+            return write!(f, "epsilon was Nan: {eps}");
+        }
+
+        if eps < N::zero() {
+            return write!(f, "epsilon cannot be negative: {eps}");
+        }
+
+        if val.is_nan() {
+            return write!(f, "value was Nan: {val}");
+        }
+
+        if target.is_nan() {
+            return write!(f, "target was Nan: {target}");
+        }
+
+        match mode {
+            WithinMode::Add => write!(f, "{val} was not within +/- {eps} of {target}"),
+            WithinMode::Mul => write!(f, "{val} was not within (1 ± {eps}) * {target}"),
+        }
+    }
+}
+
+impl<N: Display + FloatCore + fmt::Debug> core::error::Error for WithinError<N> {}
+
+/// Non-panicking counterpart to the `+` mode of [`assert_within!`], for callers that want to
+/// use `?`, accumulate multiple comparisons, or integrate with property-testing harnesses that
+/// shrink rather than abort.
+pub fn within_add<N: Display + FloatCore>(
+    val: impl Borrow<N>,
+    target: impl Borrow<N>,
+    eps: impl Borrow<N>,
+) -> Result<(), WithinError<N>> {
+    let val = *val.borrow();
+    let target = *target.borrow();
+    let eps = *eps.borrow();
+
+    let to_err = || WithinError {
+        val,
+        target,
+        eps,
+        mode: WithinMode::Add,
+    };
+
+    if eps.is_nan() || eps < N::zero() || val.is_nan() || target.is_nan() {
+        return Err(to_err());
+    }
+
+    if val < target - eps || val > target + eps {
+        return Err(to_err());
+    }
+
+    Ok(())
+}
+
+/// Non-panicking counterpart to the `~` mode of [`assert_within!`], for callers that want to
+/// use `?`, accumulate multiple comparisons, or integrate with property-testing harnesses that
+/// shrink rather than abort.
+pub fn within_mul<N: Display + FloatCore>(
+    val: impl Borrow<N>,
+    target: impl Borrow<N>,
+    eps: impl Borrow<N>,
+) -> Result<(), WithinError<N>> {
+    let val = *val.borrow();
+    let target = *target.borrow();
+    let eps = *eps.borrow();
+
+    let to_err = || WithinError {
+        val,
+        target,
+        eps,
+        mode: WithinMode::Mul,
+    };
+
+    if eps.is_nan() || eps < N::zero() || val.is_nan() || target.is_nan() {
+        return Err(to_err());
+    }
+
+    if val < (N::one() - eps) * target || val > (N::one() + eps) * target {
+        return Err(to_err());
+    }
+
+    Ok(())
+}
+
+/// Non-panicking counterpart to [`assert_within!`], expanding to [`within_add`]/[`within_mul`]
+/// and returning their `Result` instead of panicking.
+#[macro_export]
+macro_rules! check_within {
+    (+ $epsilon:expr, $val:expr, $target:expr) => {
+        $crate::within_add($val, $target, $epsilon)
+    };
+
+    (~ $epsilon:expr, $val:expr, $target:expr) => {
+        $crate::within_mul($val, $target, $epsilon)
+    };
+}
+
+/// Helper for asserting that every element of one slice-like collection is within +/- epsilon
+/// of the corresponding element of another, additively. See [`assert_within_add_impl`] for the
+/// per-element check.
+#[doc(hidden)]
+#[allow(clippy::too_many_arguments)]
+pub fn assert_within_add_slice_impl<N, I, J>(
+    file: &'static str,
+    line: u32,
+    val: I,
+    val_str: &'static str,
+    target: J,
+    target_str: &'static str,
+    eps: impl Borrow<N>,
+    context: fmt::Arguments,
+) where
+    N: Display + FloatCore,
+    I: IntoIterator,
+    I::IntoIter: ExactSizeIterator,
+    I::Item: Borrow<N>,
+    J: IntoIterator,
+    J::IntoIter: ExactSizeIterator,
+    J::Item: Borrow<N>,
+{
+    let val = val.into_iter();
+    let target = target.into_iter();
     let eps = eps.borrow();
 
     if eps.is_nan() {
-        panic!("assert_within failed at {file}:{line}:\nepsilon was Nan: {eps}\n{context}");
+        panic!("assert_all_within failed at {file}:{line}:\nepsilon was Nan: {eps}\n{context}");
     }
 
     if *eps < N::zero() {
         panic!(
-            "assert_within failed at {file}:{line}:\nEpsilon cannot be negative when used with assert_within! macro: {eps}\n{context}"
+            "assert_all_within failed at {file}:{line}:\nEpsilon cannot be negative when used with assert_all_within! macro: {eps}\n{context}"
         )
     }
 
+    let val_len = val.len();
+    let target_len = target.len();
+    if val_len != target_len {
+        panic!(
+            "assert_all_within failed at {file}:{line}:\n`{val_str}` has length {val_len} but `{target_str}` has length {target_len}\n{context}"
+        );
+    }
+
+    for (index, (val, target)) in val.zip(target).enumerate() {
+        let val = val.borrow();
+        let target = target.borrow();
+
+        if val.is_nan() {
+            panic!(
+                "assert_all_within failed at {file}:{line}:\n`{val_str}`[{index}] was Nan: {val}\n{context}"
+            );
+        }
+
+        if target.is_nan() {
+            panic!(
+                "assert_all_within failed at {file}:{line}:\n`{target_str}`[{index}] was Nan: {target}\n{context}"
+            );
+        }
+
+        if *val < *target - *eps {
+            panic!(
+                "assert_all_within failed at {file}:{line}:\n`{val_str}`[{index}] was less than `{target_str}`[{index}] - {eps})\nleft:  {val}\nright: {target}\n{context}"
+            );
+        }
+
+        if *val > *target + *eps {
+            panic!(
+                "assert_all_within failed at {file}:{line}:\n`{val_str}`[{index}] was greater than `{target_str}`[{index}] + {eps})\nleft:  {val}\nright: {target}\n{context}"
+            );
+        }
+    }
+}
+
+/// Helper for asserting that every element of one slice-like collection is within +/- epsilon
+/// of the corresponding element of another, multiplicatively. See [`assert_within_mul_impl`]
+/// for the per-element check.
+#[doc(hidden)]
+#[allow(clippy::too_many_arguments)]
+pub fn assert_within_mul_slice_impl<N, I, J>(
+    file: &'static str,
+    line: u32,
+    val: I,
+    val_str: &'static str,
+    target: J,
+    target_str: &'static str,
+    eps: impl Borrow<N>,
+    context: fmt::Arguments,
+) where
+    N: Display + FloatCore,
+    I: IntoIterator,
+    I::IntoIter: ExactSizeIterator,
+    I::Item: Borrow<N>,
+    J: IntoIterator,
+    J::IntoIter: ExactSizeIterator,
+    J::Item: Borrow<N>,
+{
+    let val = val.into_iter();
+    let target = target.into_iter();
+    let eps = eps.borrow();
+
+    if eps.is_nan() {
+        panic!("assert_all_within failed at {file}:{line}:\nepsilon was Nan: {eps}\n{context}");
+    }
+
+    if *eps < N::zero() {
+        panic!(
+            "assert_all_within failed at {file}:{line}:\nEpsilon cannot be negative when used with assert_all_within! macro: {eps}\n{context}"
+        )
+    }
+
+    let val_len = val.len();
+    let target_len = target.len();
+    if val_len != target_len {
+        panic!(
+            "assert_all_within failed at {file}:{line}:\n`{val_str}` has length {val_len} but `{target_str}` has length {target_len}\n{context}"
+        );
+    }
+
+    let one_minus_eps = N::one() - *eps;
+    let one_plus_eps = N::one() + *eps;
+
+    for (index, (val, target)) in val.zip(target).enumerate() {
+        let val = val.borrow();
+        let target = target.borrow();
+
+        if val.is_nan() {
+            panic!(
+                "assert_all_within failed at {file}:{line}:\n`{val_str}`[{index}] was Nan: {val}\n{context}"
+            );
+        }
+
+        if target.is_nan() {
+            panic!(
+                "assert_all_within failed at {file}:{line}:\n`{target_str}`[{index}] was Nan: {target}\n{context}"
+            );
+        }
+
+        if *val < one_minus_eps * *target {
+            panic!(
+                "assert_all_within failed at {file}:{line}:\n`{val_str}`[{index}] was less than (1 ± {eps}) * `{target_str}`[{index}]\nleft:  {val}\nright: {target}\n{context}"
+            );
+        }
+
+        if *val > one_plus_eps * *target {
+            panic!(
+                "assert_all_within failed at {file}:{line}:\n`{val_str}`[{index}] was greater than (1 ± {eps}) * `{target_str}`[{index}]\nleft:  {val}\nright: {target}\n{context}"
+            );
+        }
+    }
+}
+
+/// Bit-pattern helper backing the `ulps` comparison mode. `FloatCore` exposes `integer_decode`
+/// but not raw bits, so this is implemented directly for `f32`/`f64`: the IEEE bit pattern is
+/// remapped to a signed integer that is monotonic in the float's value (a negative value maps
+/// to `MIN_INT - bits`, a non-negative value maps to itself), so ULP distance between two
+/// floats reduces to a plain integer subtraction.
+#[doc(hidden)]
+pub trait UlpsKey: Copy {
+    fn ulps_key(self) -> i64;
+}
+
+impl UlpsKey for f32 {
+    fn ulps_key(self) -> i64 {
+        let bits = i64::from(self.to_bits() as i32);
+        if bits < 0 {
+            i64::from(i32::MIN) - bits
+        } else {
+            bits
+        }
+    }
+}
+
+impl UlpsKey for f64 {
+    fn ulps_key(self) -> i64 {
+        let bits = self.to_bits() as i64;
+        if bits < 0 { i64::MIN - bits } else { bits }
+    }
+}
+
+/// Helper for asserting that an f64 value is within `max_ulps` ("units in the last place") of
+/// another. `+0.0` and `-0.0` are zero ulps apart; values on opposite sides of zero (other than
+/// signed zeros) are treated as infinitely far apart rather than wrapping around.
+#[doc(hidden)]
+#[allow(clippy::too_many_arguments)]
+pub fn assert_within_ulps_impl<N: Display + FloatCore + UlpsKey>(
+    file: &'static str,
+    line: u32,
+    val: impl Borrow<N>,
+    val_str: &'static str,
+    target: impl Borrow<N>,
+    target_str: &'static str,
+    max_ulps: u64,
+    context: fmt::Arguments,
+) {
+    let val = val.borrow();
+    let target = target.borrow();
+
     if val.is_nan() {
         panic!("assert_within failed at {file}:{line}:\n`{val_str}` was Nan: {val}\n{context}");
     }
@@ -110,17 +538,18 @@ pub fn assert_within_mul_impl<N: Display + FloatCore>(
         );
     }
 
-    let one_minus_eps = N::one() - *eps;
-    if *val < one_minus_eps * *target {
+    let opposite_signs = val.is_sign_negative() != target.is_sign_negative();
+    if opposite_signs && !(val.is_zero() && target.is_zero()) {
         panic!(
-            "assert_within failed at {file}:{line}:\n`{val_str}` was less than (1 ± {eps}) * `{target_str}`\nleft:  {val}\nright: {target}\n{context}"
+            "assert_within failed at {file}:{line}:\n`{val_str}` and `{target_str}` are on opposite sides of zero, so they are infinitely far apart in ulps\nleft:  {val}\nright: {target}\n{context}"
         );
     }
 
-    let one_plus_eps = N::one() + *eps;
-    if *val > one_plus_eps * *target {
+    let distance = (i128::from(val.ulps_key()) - i128::from(target.ulps_key())).unsigned_abs();
+
+    if distance > u128::from(max_ulps) {
         panic!(
-            "assert_within failed at {file}:{line}:\n`{val_str}` was greater than (1 ± {eps}) * `{target_str}`\nleft:  {val}\nright: {target}\n{context}"
+            "assert_within failed at {file}:{line}:\n`{val_str}` was not within {max_ulps} ulps of `{target_str}`\nleft:  {val}\nright: {target}\ndistance: {distance} ulps\n{context}"
         );
     }
 }
@@ -128,7 +557,118 @@ pub fn assert_within_mul_impl<N: Display + FloatCore>(
 #[macro_export]
 macro_rules! assert_within {
     (+ $epsilon:expr, $val:expr, $target:expr) => {
-        $crate::test_utils::assert_within_add_impl(
+        $crate::assert_within_add_impl(
+            file!(),
+            line!(),
+            $val,
+            stringify!($val),
+            $target,
+            stringify!($target),
+            $epsilon,
+            format_args!(""),
+        )
+    };
+
+    (+ $epsilon:expr, $val:expr, $target:expr, $($fmt_args:tt)*) => {
+        $crate::assert_within_add_impl(
+            file!(),
+            line!(),
+            $val,
+            stringify!($val),
+            $target,
+            stringify!($target),
+            $epsilon,
+            format_args!($($fmt_args)*),
+        )
+    };
+
+    (~ $epsilon:expr, $val:expr, $target:expr) => {
+        $crate::assert_within_mul_impl(
+            file!(),
+            line!(),
+            $val,
+            stringify!($val),
+            $target,
+            stringify!($target),
+            $epsilon,
+            format_args!(""),
+        )
+    };
+
+    (~ $epsilon:expr, $val:expr, $target:expr, $($fmt_args:tt)*) => {
+        $crate::assert_within_mul_impl(
+            file!(),
+            line!(),
+            $val,
+            stringify!($val),
+            $target,
+            stringify!($target),
+            $epsilon,
+            format_args!($($fmt_args)*),
+        )
+    };
+
+    (+~ $atol:expr, $rtol:expr, $val:expr, $target:expr) => {
+        $crate::assert_within_close_impl(
+            file!(),
+            line!(),
+            $val,
+            stringify!($val),
+            $target,
+            stringify!($target),
+            $atol,
+            $rtol,
+            format_args!(""),
+        )
+    };
+
+    (+~ $atol:expr, $rtol:expr, $val:expr, $target:expr, $($fmt_args:tt)*) => {
+        $crate::assert_within_close_impl(
+            file!(),
+            line!(),
+            $val,
+            stringify!($val),
+            $target,
+            stringify!($target),
+            $atol,
+            $rtol,
+            format_args!($($fmt_args)*),
+        )
+    };
+
+    (ulps $max_ulps:expr, $val:expr, $target:expr) => {
+        $crate::assert_within_ulps_impl(
+            file!(),
+            line!(),
+            $val,
+            stringify!($val),
+            $target,
+            stringify!($target),
+            $max_ulps,
+            format_args!(""),
+        )
+    };
+
+    (ulps $max_ulps:expr, $val:expr, $target:expr, $($fmt_args:tt)*) => {
+        $crate::assert_within_ulps_impl(
+            file!(),
+            line!(),
+            $val,
+            stringify!($val),
+            $target,
+            stringify!($target),
+            $max_ulps,
+            format_args!($($fmt_args)*),
+        )
+    };
+}
+
+/// Elementwise counterpart to [`assert_within!`] for comparing two slices or iterables of
+/// floats, e.g. `assert_all_within!(+0.001, &computed, &expected, "mismatch in row {i}")`.
+#[macro_export]
+macro_rules! assert_all_within {
+    (+ $epsilon:expr, $val:expr, $target:expr) => {
+        $crate::assert_within_add_slice_impl(
             file!(),
             line!(),
             $val,
@@ -141,7 +681,7 @@ macro_rules! assert_within {
     };
 
     (+ $epsilon:expr, $val:expr, $target:expr, $($fmt_args:tt)*) => {
-        $crate::test_utils::assert_within_add_impl(
+        $crate::assert_within_add_slice_impl(
             file!(),
             line!(),
             $val,
@@ -154,7 +694,7 @@ macro_rules! assert_within {
     };
 
     (~ $epsilon:expr, $val:expr, $target:expr) => {
-        $crate::test_utils::assert_within_mul_impl(
+        $crate::assert_within_mul_slice_impl(
             file!(),
             line!(),
             $val,
@@ -167,7 +707,7 @@ macro_rules! assert_within {
     };
 
     (~ $epsilon:expr, $val:expr, $target:expr, $($fmt_args:tt)*) => {
-        $crate::test_utils::assert_within_mul_impl(
+        $crate::assert_within_mul_slice_impl(
             file!(),
             line!(),
             $val,
@@ -179,3 +719,149 @@ macro_rules! assert_within {
         )
     };
 }
+
+#[cfg(test)]
+extern crate std;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn add_and_mul_pass() {
+        assert_within!(+0.001, 1.0, 1.0005_f64);
+        assert_within!(~0.05, 1.0, 1.02_f64);
+    }
+
+    #[test]
+    #[should_panic(expected = "overshoot: 0.999")]
+    fn add_failure_reports_diff_allowed_and_overshoot() {
+        assert_within!(+0.001, 1.0_f64, 2.0_f64);
+    }
+
+    #[test]
+    #[should_panic(expected = "relative error: 0.5")]
+    fn mul_failure_reports_actual_relative_error() {
+        assert_within!(~0.01, 1.0_f64, 2.0_f64);
+    }
+
+    #[test]
+    #[should_panic(expected = "Nan")]
+    fn add_traps_nan_value() {
+        assert_within!(+0.001, f64::NAN, 1.0_f64);
+    }
+
+    #[test]
+    #[should_panic(expected = "Nan")]
+    fn add_traps_nan_epsilon() {
+        assert_within!(+f64::NAN, 1.0_f64, 1.0_f64);
+    }
+
+    #[test]
+    #[should_panic(expected = "cannot be negative")]
+    fn mul_traps_negative_epsilon() {
+        assert_within!(~-0.1, 1.0_f64, 1.0_f64);
+    }
+
+    #[test]
+    fn close_mode_holds_near_zero() {
+        // Purely multiplicative tolerance collapses to nothing when target is zero; atol keeps
+        // this meaningful.
+        assert_within!(+~ 1e-6, 0.1, 1e-9_f64, 0.0_f64);
+    }
+
+    #[test]
+    #[should_panic(expected = "Nan")]
+    fn close_mode_traps_nan_atol() {
+        assert_within!(+~ f64::NAN, 0.1, 1.0_f64, 1.0_f64);
+    }
+
+    #[test]
+    #[should_panic(expected = "Nan")]
+    fn close_mode_traps_nan_rtol() {
+        assert_within!(+~ 1e-6, f64::NAN, 1.0_f64, 1.0_f64);
+    }
+
+    #[test]
+    fn slice_modes_pass() {
+        let a = [1.0_f64, 2.0, 3.0];
+        let b = [1.0001_f64, 2.0001, 3.0001];
+        assert_all_within!(+0.001, &a, &b);
+        assert_all_within!(~0.01, &a, &b);
+    }
+
+    #[test]
+    fn iterator_modes_pass() {
+        let a = std::vec![1.0_f64, 2.0, 3.0];
+        let b = [1.0001_f64, 2.0001, 3.0001];
+        assert_all_within!(+0.001, a.iter().copied(), b.iter().map(|x| x + 0.0));
+    }
+
+    #[test]
+    #[should_panic(expected = "has length")]
+    fn slice_length_mismatch() {
+        let a = [1.0_f64, 2.0];
+        let b = [1.0_f64, 2.0, 3.0];
+        assert_all_within!(+0.001, &a, &b);
+    }
+
+    #[test]
+    #[should_panic(expected = "[2]")]
+    fn slice_reports_failing_index() {
+        let a = [1.0_f64, 2.0, 3.0];
+        let b = [1.0_f64, 2.0, 30.0];
+        assert_all_within!(+0.001, &a, &b);
+    }
+
+    #[test]
+    fn ulps_signed_zero_is_zero_distance() {
+        assert_within!(ulps 0, 0.0_f64, -0.0_f64);
+    }
+
+    #[test]
+    #[should_panic(expected = "infinitely far apart")]
+    fn ulps_opposite_signs_are_infinitely_far() {
+        assert_within!(ulps u64::MAX, 1.0_f64, -1.0_f64);
+    }
+
+    #[test]
+    fn ulps_within_distance_passes() {
+        let a = 1.0_f64;
+        let b = f64::from_bits(a.to_bits() + 2);
+        assert_within!(ulps 4, a, b);
+    }
+
+    #[test]
+    #[should_panic(expected = "not within")]
+    fn ulps_beyond_distance_fails() {
+        let a = 1.0_f64;
+        let b = f64::from_bits(a.to_bits() + 10);
+        assert_within!(ulps 4, a, b);
+    }
+
+    #[test]
+    fn check_within_is_ok_on_success_and_err_on_failure() {
+        assert!(check_within!(+0.001, 1.0_f64, 1.0005_f64).is_ok());
+        assert!(check_within!(~0.05, 1.0_f64, 1.02_f64).is_ok());
+        assert!(within_add(1.0_f64, 2.0_f64, 0.001_f64).is_err());
+        assert!(within_mul(1.0_f64, 2.0_f64, 0.001_f64).is_err());
+    }
+
+    #[test]
+    fn within_error_propagates_with_question_mark() {
+        fn check() -> Result<(), WithinError<f64>> {
+            within_add(1.0_f64, 1.0005_f64, 0.001_f64)?;
+            within_mul(1.0_f64, 2.0_f64, 0.001_f64)?;
+            Ok(())
+        }
+
+        assert!(check().is_err());
+    }
+
+    #[test]
+    fn within_error_display_is_informative() {
+        let err = within_add(1.0_f64, 2.0_f64, 0.001_f64).unwrap_err();
+        let message = std::format!("{err}");
+        assert!(message.contains("not within"));
+    }
+}